@@ -8,6 +8,12 @@
 //! * innerTextからDOM取得
 //! * タブを閉じる
 //! * 一連の操作をまとめて実行
+//! * User-Agent/追加ヘッダーの上書き、リソースブロック、レスポンスボディのキャプチャ
+//! * Extractorによるページの構造化JSON抽出
+//! * リンクを辿る再帰的なクロール(訪問済みURLの重複排除、深さ制限付き)
+//! * Cookieのエクスポート/インポート、スクリーンショット/PDF出力
+//! * select要素の選択肢取得、および値/表示テキストによる選択
+//! * 文脈付きの`ScrapeError`によるエラーハンドリング
 //!
 //! ## サンプルコード
 //! ```
@@ -26,6 +32,9 @@
 //!         headless: Some(false),
 //!         window_size: Some((1920, 1080)),
 //!         port_number: None,
+//!         user_agent: None,
+//!         extra_headers: None,
+//!         block_resource_types: None,
 //!     };
 //!     let wrapper = scraping_wrapper::ScrapingWrapper::new(scrape_options)?;
 //!
@@ -59,12 +68,18 @@
 //! }
 //! ```
 
+use headless_chrome::protocol::cdp::Fetch::{ErrorReason, RequestPausedDecision};
+use headless_chrome::protocol::cdp::Network;
+use headless_chrome::protocol::cdp::Page::{CaptureScreenshotFormatOption, PrintToPdfOptions};
 use headless_chrome::{Browser, Element, LaunchOptionsBuilder, Tab};
-use std::collections::HashMap;
+use rand::Rng;
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
 use std::time::Duration;
+use url::Url;
 
 /// スクレイピングのオプションを保持する構造体
 pub struct ScrapeOption {
@@ -73,6 +88,9 @@ pub struct ScrapeOption {
     pub window_size: Option<(u32, u32)>,
     pub port_number: Option<u16>, // 実行中のブラウザを使用する場合、ポート番号を定義
                                   // `--remote-debugging-port`でブラウザを実行することで可能
+    pub user_agent: Option<String>, // 送信するUser-Agentを上書きする
+    pub extra_headers: Option<HashMap<String, String>>, // すべてのリクエストに付与する追加ヘッダー
+    pub block_resource_types: Option<Vec<String>>, // ブロックするリソースタイプ(例: "Image", "Font", "Stylesheet")
 }
 
 /// スクレイピング操作を管理するためのメイン構造体
@@ -81,6 +99,43 @@ pub struct ScrapingWrapper {
     browser: Browser, // ブラウザインスタンス
     tab: Arc<Tab>,                     // 操作用のタブインスタンス
     dom_defs: HashMap<String, String>, // DOM定義
+    extractors: Mutex<Vec<Arc<dyn Extractor>>>, // 登録済みのExtractor(優先度の高い順)
+    // capture_responseが監視対象として登録した部分文字列 -> 受信済みのレスポンスボディ。
+    // まだ受信していない場合は`None`。監視していない部分文字列のレスポンスはボディを
+    // 取得・保持しない(全レスポンスのボディを無条件にキャプチャするとGetResponseBodyの
+    // 呼び出しとメモリ消費がタブの寿命全体で無制限に積み上がってしまうため)。
+    captured_responses: Arc<Mutex<HashMap<String, Option<String>>>>,
+}
+
+/// ページを構造化された`serde_json::Value`へ変換するExtractor
+///
+/// サイトごとに専用のExtractorを実装して`register_extractor`で登録すると、
+/// `extract`が現在のタブのURLに一致する最初のExtractorを選んで実行する。
+pub trait Extractor: Send + Sync {
+    /// このExtractorが`url`のページを処理できるかどうか
+    fn can_handle(&self, url: &str) -> bool;
+    /// ページから構造化データを抽出する
+    fn extract(&self, wrapper: &ScrapingWrapper) -> Result<serde_json::Value, ScrapeError>;
+}
+
+/// `dom_defs`に定義された全セレクタのinnerTextを集めてJSONにするデフォルトのExtractor
+///
+/// どのURLに対しても`can_handle`が`true`を返すため、常にフォールバックとして機能する。
+pub struct DomDefsExtractor;
+
+impl Extractor for DomDefsExtractor {
+    fn can_handle(&self, _url: &str) -> bool {
+        true
+    }
+
+    fn extract(&self, wrapper: &ScrapingWrapper) -> Result<serde_json::Value, ScrapeError> {
+        let mut record = serde_json::Map::new();
+        for key in wrapper.dom_defs.keys() {
+            let text = wrapper.get_inner_text(key)?;
+            record.insert(key.clone(), serde_json::Value::String(text));
+        }
+        Ok(serde_json::Value::Object(record))
+    }
 }
 
 /// 実行する操作を表す構造体
@@ -99,46 +154,243 @@ pub enum OperationMethod {
     Fill,  // テキストフィールドに入力
 }
 
+/// クリック後に満たされるべき条件
+#[derive(Debug)]
+pub enum WaitFor {
+    /// このCSSセレクタの要素が出現するまで待つ
+    Selector(String),
+    /// URLが変化するまで待つ
+    UrlChange,
+    /// クリックした要素がDOMから外れる(stale)まで待つ
+    Stale,
+}
+
+/// `click_with`の挙動を制御するオプション
+#[derive(Debug, Default)]
+pub struct ClickOptions {
+    pub wait_for: Option<WaitFor>,
+}
+
+/// `crawl`の挙動を制御するオプション
+pub struct CrawlOptions {
+    pub max_depth: u32,          // シードURLからの最大階層
+    pub same_host_only: bool,    // シードURLと同じホストのリンクのみ辿るかどうか
+    pub url_filter: Option<Regex>, // このパターンにマッチするURLのみ辿る
+    pub exclude_filter: Option<Regex>, // このパターンにマッチするURLは辿らない(url_filterとは独立に適用)
+    pub min_delay_ms: u64,        // ページ遷移ごとの最小待機時間
+    pub max_delay_ms: u64,        // ページ遷移ごとの最大待機時間
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        CrawlOptions {
+            max_depth: 1,
+            same_host_only: true,
+            url_filter: None,
+            exclude_filter: None,
+            min_delay_ms: 500,
+            max_delay_ms: 1500,
+        }
+    }
+}
+
+/// `crawl`の実行結果
+#[derive(Debug, Default)]
+pub struct CrawlSummary {
+    pub visited: Vec<String>,            // 訪問に成功したURL
+    pub errors: HashMap<String, String>, // 失敗したURLとそのエラー内容
+}
+
+/// このクレート共通のエラー型
+///
+/// `Box<dyn Error>`は「どの`dom_defs`キーで」「どのURLで」失敗したのかが分からず
+/// 診断しにくいため、文脈を保持した専用のエラー型を用意する。下位ライブラリの
+/// エラーは`Other`、もしくは各バリアントの`source`としてそのまま保持する。
+#[derive(Debug)]
+pub enum ScrapeError {
+    /// ブラウザの起動に失敗した
+    BrowserLaunch(Box<dyn Error + Send + Sync>),
+    /// 実行中のブラウザへの接続に失敗した
+    Connect(Box<dyn Error + Send + Sync>),
+    /// ページ遷移に失敗した
+    Navigation {
+        url: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// `dom_defs`に定義されていないキーが指定された
+    SelectorNotDefined(String),
+    /// 要素の出現待ちがタイムアウトした
+    ElementTimeout { target: String, selector: String },
+    /// 要素の操作(クリック/入力など)に失敗した
+    Interaction {
+        target: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// リモートブラウザの情報取得に失敗した(`port_number`接続時のブラウザ情報/WebSocket URL取得)
+    RemoteInfo(String),
+    /// JSの評価結果が期待した形式ではなかった
+    JsEvaluation { target: String, message: String },
+    /// 現在のURLにマッチする`Extractor`が登録されていない
+    NoMatchingExtractor(String),
+    /// `operate`中の特定のステップが失敗した
+    OperateStep {
+        index: usize,
+        method: String,
+        source: Box<ScrapeError>,
+    },
+    /// リトライ回数を超えた
+    RetryExhausted {
+        attempts: u8,
+        source: Box<ScrapeError>,
+    },
+    /// 引数が不正だった(例: `retry`に`retries: 0`を渡した)
+    InvalidArgument(String),
+    /// 上記のいずれにも当てはまらないエラー
+    Other(Box<dyn Error + Send + Sync>),
+}
+
+impl ScrapeError {
+    /// 下位ライブラリのエラーを`ScrapeError::Other`として包む
+    fn other<E: Error + Send + Sync + 'static>(source: E) -> ScrapeError {
+        ScrapeError::Other(Box::new(source))
+    }
+}
+
+impl std::fmt::Display for ScrapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrapeError::BrowserLaunch(source) => write!(f, "ブラウザの起動に失敗しました: {}", source),
+            ScrapeError::Connect(source) => write!(f, "ブラウザへの接続に失敗しました: {}", source),
+            ScrapeError::Navigation { url, source } => {
+                write!(f, "ページ遷移に失敗しました (url: {}): {}", url, source)
+            }
+            ScrapeError::SelectorNotDefined(target) => {
+                write!(f, "dom_defsに'{}'というキーが定義されていません", target)
+            }
+            ScrapeError::ElementTimeout { target, selector } => write!(
+                f,
+                "要素の出現待ちがタイムアウトしました (target: {}, selector: {})",
+                target, selector
+            ),
+            ScrapeError::Interaction { target, source } => {
+                write!(f, "'{}'の操作に失敗しました: {}", target, source)
+            }
+            ScrapeError::RemoteInfo(message) => {
+                write!(f, "リモートブラウザの情報取得に失敗しました: {}", message)
+            }
+            ScrapeError::JsEvaluation { target, message } => {
+                write!(f, "'{}'に対するJSの評価結果が不正です: {}", target, message)
+            }
+            ScrapeError::NoMatchingExtractor(url) => {
+                write!(f, "URL '{}' に一致するExtractorが見つかりません", url)
+            }
+            ScrapeError::OperateStep {
+                index,
+                method,
+                source,
+            } => write!(
+                f,
+                "operateの{}番目のステップ({})が失敗しました: {}",
+                index, method, source
+            ),
+            ScrapeError::RetryExhausted { attempts, source } => write!(
+                f,
+                "リトライ回数({}回)を超えました。最後のエラー: {}",
+                attempts, source
+            ),
+            ScrapeError::InvalidArgument(message) => {
+                write!(f, "引数が不正です: {}", message)
+            }
+            ScrapeError::Other(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl Error for ScrapeError {}
+
 /// 遅延を伴うタスクを複数回リトライする関数
-pub fn retry<F, T>(mut task: F, retries: u8, delay: u64) -> Result<T, Box<dyn Error>>
+///
+/// 失敗した場合、最後に発生したエラーと試行回数を`RetryExhausted`として報告する。
+///
+/// `retries`に0を渡した場合はタスクを一度も実行せず`InvalidArgument`を返す。
+pub fn retry<F, T>(mut task: F, retries: u8, delay: u64) -> Result<T, ScrapeError>
 where
-    F: FnMut() -> Result<T, Box<dyn Error>>,
+    F: FnMut() -> Result<T, ScrapeError>,
 {
-    let mut attempts = 0;
+    if retries == 0 {
+        return Err(ScrapeError::InvalidArgument(
+            "retriesには1以上を指定してください(0ではタスクを一度も実行できません)".to_string(),
+        ));
+    }
+
+    let mut attempts: u8 = 0;
+    let mut last_error = None;
     while attempts < retries {
         match task() {
             Ok(result) => return Ok(result),
-            Err(_) if attempts < retries - 1 => {
-                sleep(Duration::from_secs(delay)); // リトライ前にスリープ
+            Err(e) => {
                 attempts += 1;
+                last_error = Some(e);
+                if attempts < retries {
+                    sleep(Duration::from_secs(delay)); // リトライ前にスリープ
+                }
             }
-            Err(e) => return Err(e),
         }
     }
-    Err("リトライ回数を超えました".into()) // リトライ回数を超えた場合にエラーを返す
+    Err(ScrapeError::RetryExhausted {
+        attempts,
+        source: Box::new(last_error.expect("リトライが1回も実行されていません")),
+    })
+}
+
+/// `export_cookies`が返す`Network::Cookie`を`set_cookies`が要求する`Network::CookieParam`へ変換する
+///
+/// `url`は指定せず、代わりに`domain`/`path`を引き継ぐことでスコープを再現する。
+fn cookie_to_param(cookie: Network::Cookie) -> Network::CookieParam {
+    Network::CookieParam {
+        name: cookie.name,
+        value: cookie.value,
+        url: None,
+        domain: Some(cookie.domain),
+        path: Some(cookie.path),
+        secure: Some(cookie.secure),
+        http_only: Some(cookie.http_only),
+        same_site: cookie.same_site,
+        expires: Some(cookie.expires),
+        priority: Some(cookie.priority),
+        same_party: cookie.same_party,
+        source_scheme: cookie.source_scheme,
+        source_port: Some(cookie.source_port),
+        partition_key: cookie.partition_key,
+    }
 }
 
 /// ScrapingWrapperの実装
 impl ScrapingWrapper {
     /// ScrapingWrapperのコンストラクタ
-    pub fn new(opt: ScrapeOption) -> Result<ScrapingWrapper, Box<dyn Error>> {
+    pub fn new(opt: ScrapeOption) -> Result<ScrapingWrapper, ScrapeError> {
         let dom_defs = opt.dom_defs.unwrap_or(HashMap::new()); // 提供されたDOM定義または空の定義を使用
 
         let browser = match opt.port_number {
             Some(port_number) => {
                 let browser_info_url =
                     format!("http://localhost:{}/json", port_number.to_string(),);
-                let response = reqwest::blocking::get(&browser_info_url)?;
-                let browser_info: serde_json::Value = serde_json::from_str(&response.text()?)?;
+                let response =
+                    reqwest::blocking::get(&browser_info_url).map_err(ScrapeError::other)?;
+                let text = response.text().map_err(ScrapeError::other)?;
+                let browser_info: serde_json::Value =
+                    serde_json::from_str(&text).map_err(ScrapeError::other)?;
                 let websocket_url = browser_info
                     .as_array()
-                    .ok_or("ブラウザ情報が配列ではありません")?
+                    .ok_or_else(|| ScrapeError::RemoteInfo("ブラウザ情報が配列ではありません".to_string()))?
                     .iter()
                     .find(|&info| info["type"] == "page")
                     .and_then(|info| info["webSocketDebuggerUrl"].as_str())
-                    .ok_or("タイプ 'page' のWebSocket URLが見つかりません")?
+                    .ok_or_else(|| {
+                        ScrapeError::RemoteInfo("タイプ 'page' のWebSocket URLが見つかりません".to_string())
+                    })?
                     .to_string();
-                Browser::connect(websocket_url)?
+                Browser::connect(websocket_url).map_err(|e| ScrapeError::Connect(Box::new(e)))?
             }
             None => {
                 let headless = opt.headless.unwrap_or(true); // 指定がない場合はデフォルトでヘッドレスモード
@@ -150,25 +402,99 @@ impl ScrapingWrapper {
                     .window_size(window_size) // ウィンドウサイズを設定
                     .build()
                     .expect("ブラウザ起動オプションの構築に失敗しました");
-                Browser::new(launch_options)? // 新しいブラウザインスタンスを作成
+                Browser::new(launch_options).map_err(|e| ScrapeError::BrowserLaunch(Box::new(e)))? // 新しいブラウザインスタンスを作成
             }
         };
         let tabs = browser.get_tabs();
         let tab = tabs.lock().unwrap().last().unwrap().clone();
 
+        if let Some(user_agent) = opt.user_agent {
+            tab.set_user_agent(&user_agent, None, None)
+                .map_err(ScrapeError::other)?; // User-Agentを上書き
+        }
+
+        if let Some(extra_headers) = opt.extra_headers {
+            tab.call_method(Network::SetExtraHTTPHeaders {
+                headers: Network::Headers(extra_headers),
+            })
+            .map_err(ScrapeError::other)?; // すべてのリクエストに追加ヘッダーを付与
+        }
+
+        if let Some(block_resource_types) = opt.block_resource_types {
+            tab.enable_request_interception(Arc::new(move |_transport, _session_id, event| {
+                let resource_type = event.params.resource_type.to_string();
+                if block_resource_types
+                    .iter()
+                    .any(|blocked| blocked.eq_ignore_ascii_case(&resource_type))
+                {
+                    RequestPausedDecision::Fail(ErrorReason::Failed) // 指定タイプのリソースを遮断
+                } else {
+                    RequestPausedDecision::Continue(None)
+                }
+            }))
+            .map_err(ScrapeError::other)?;
+        }
+
+        tab.call_method(Network::Enable {
+            max_total_buffer_size: None,
+            max_resource_buffer_size: None,
+            max_post_data_size: None,
+        })
+        .map_err(ScrapeError::other)?;
+
+        // `capture_response`が呼ばれるたびにリスナーを追加すると、呼ぶたびに増えて
+        // 二重三重に同じボディが処理されてしまう。インスタンス生成時に一度だけ登録し、
+        // `capture_response`が監視対象として登録した部分文字列にマッチするレスポンスだけ
+        // ボディを取得して貯めておく方式にする。監視対象として登録される前に届いたレスポンスは
+        // 記録されないため、`capture_response`は対象の操作より前に一度呼んでおく必要がある。
+        // 監視対象外のレスポンスはGetResponseBodyすら呼ばず、
+        // 全レスポンスを無条件にキャプチャすることによるコスト増を避ける。
+        let captured_responses: Arc<Mutex<HashMap<String, Option<String>>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+        let captured_responses_for_listener = captured_responses.clone();
+        let tab_for_listener = tab.clone();
+        tab.add_event_listener(Arc::new(move |event: &Network::events::ResponseReceivedEvent| {
+            let matched_substring = {
+                let watched = captured_responses_for_listener.lock().unwrap();
+                watched
+                    .keys()
+                    .find(|substring| event.params.response.url.contains(substring.as_str()))
+                    .cloned()
+            };
+            if let Some(matched_substring) = matched_substring {
+                if let Ok(response) = tab_for_listener.call_method(Network::GetResponseBody {
+                    request_id: event.params.request_id.clone(),
+                }) {
+                    captured_responses_for_listener
+                        .lock()
+                        .unwrap()
+                        .insert(matched_substring, Some(response.body));
+                }
+            }
+        }))
+        .map_err(ScrapeError::other)?;
+
         Ok(ScrapingWrapper {
             browser,
             tab: tab,
             dom_defs,
+            extractors: Mutex::new(vec![Arc::new(DomDefsExtractor)]),
+            captured_responses,
         }) // 新しいインスタンスを返す
     }
 
     /// URLに移動
-    pub fn go(&self, url: &str) -> Result<(), Box<dyn Error>> {
+    pub fn go(&self, url: &str) -> Result<(), ScrapeError> {
         let func = || {
             // let tab = self.browser.new_tab()?; // 新しいタブを開く // TODO: 削除
             // self.tab = Some(tab); // TODO: 削除
-            self.tab.navigate_to(url)?.wait_until_navigated()?; // 移動して待機
+            self.tab
+                .navigate_to(url)
+                .and_then(|tab| tab.wait_until_navigated()) // 移動して待機
+                .map_err(|e| ScrapeError::Navigation {
+                    url: url.to_string(),
+                    source: Box::new(e),
+                })?;
             Ok(())
         };
         let result = retry(func, 5, 2)?; // 必要に応じてリトライ
@@ -176,33 +502,144 @@ impl ScrapingWrapper {
     }
 
     /// ダイアログを表示してユーザーの操作を待機
-    pub fn show_dialog_and_wait(&self, message: Option<&str>) -> Result<(), Box<dyn Error>> {
+    pub fn show_dialog_and_wait(&self, message: Option<&str>) -> Result<(), ScrapeError> {
         let dialog_message = message.unwrap_or("続行するにはOKを押してください。"); // デフォルトメッセージ
         self.tab
-            .evaluate(&format!("alert('{}');", dialog_message), true)?; // アラートダイアログを表示
+            .evaluate(&format!("alert('{}');", dialog_message), true)
+            .map_err(ScrapeError::other)?; // アラートダイアログを表示
         Ok(())
     }
 
     /// ターゲット識別子でDOM要素を取得
-    pub fn get_dom(&self, target: &str) -> Result<Element, Box<dyn Error>> {
+    pub fn get_dom(&self, target: &str) -> Result<Element, ScrapeError> {
         let func = || {
-            let selector = self.dom_defs.get(target).unwrap(); // 定義からセレクタを取得
+            // 定義からセレクタを取得。未定義のキーは`SelectorNotDefined`として報告する
+            let selector = self
+                .dom_defs
+                .get(target)
+                .ok_or_else(|| ScrapeError::SelectorNotDefined(target.to_string()))?;
             let element = self
                 .tab
-                .wait_for_element_with_custom_timeout(selector, Duration::from_secs(1))?; // 要素を待機
-            element.scroll_into_view()?; // 要素を表示領域にスクロール
+                .wait_for_element_with_custom_timeout(selector, Duration::from_secs(1))
+                .map_err(|_| ScrapeError::ElementTimeout {
+                    target: target.to_string(),
+                    selector: selector.clone(),
+                })?; // 要素を待機
+            element.scroll_into_view().map_err(ScrapeError::other)?; // 要素を表示領域にスクロール
             Ok(element)
         };
         let result = retry(func, 5, 2)?; // 必要に応じてリトライ
         Ok(result)
     }
 
-    /// 指定された要素をクリック
-    pub fn click(&self, element: Element) -> Result<(), Box<dyn Error>> {
+    /// 指定された要素をクリック(デフォルトの待機条件を使用)
+    pub fn click(&self, element: Element) -> Result<(), ScrapeError> {
+        self.click_with(element, ClickOptions::default())
+    }
+
+    /// 指定された要素をクリックし、`options`で指定された条件が満たされるまで待機する
+    ///
+    /// ネイティブの`click()`だけでは、要素がオーバーレイに覆われていたり
+    /// `pointer-events: none`だったりする場合に何も起きないことがあるため、
+    /// ヒットテストで要素がクリックを受けられないと判定された場合や、ネイティブクリックの
+    /// イベントが要素まで届かなかった場合に`this.click()`をJS経由で発行するフォールバックを行う。
+    pub fn click_with(&self, element: Element, options: ClickOptions) -> Result<(), ScrapeError> {
+        let target = "click_with対象の要素"; // `Interaction`のエラーメッセージ用のラベル
+
+        // 要素に対して任意のJSを評価し、戻り値(なければnull)を取り出す補助クロージャ
+        let call_js = |script: &str| -> Result<serde_json::Value, ScrapeError> {
+            let result = element
+                .call_js_fn(script, vec![], false)
+                .map_err(ScrapeError::other)?;
+            Ok(result.value.unwrap_or(serde_json::Value::Null))
+        };
+
         let func = || {
-            element.click()?; // クリックを実行
-            sleep(Duration::from_secs(1));
-            self.tab.wait_until_navigated()?; // ナビゲーションを待機
+            element.scroll_into_view().map_err(ScrapeError::other)?; // 要素を表示領域にスクロール
+
+            // バウンディングボックスが空でない(=画面上に表示されている)ことを確認
+            let box_model = element.get_box_model().map_err(ScrapeError::other)?;
+            if box_model.width <= 0.0 || box_model.height <= 0.0 {
+                return Err(ScrapeError::Interaction {
+                    target: target.to_string(),
+                    source: "クリック対象の要素が表示されていません".into(),
+                });
+            }
+
+            // pointer-events:noneだったり、別の要素(オーバーレイなど)に覆われていたりすると
+            // ネイティブクリックが違う要素に命中してしまうため、先にヒットテストしておく
+            let is_hit_target = call_js(
+                "function() { \
+                    const style = window.getComputedStyle(this); \
+                    if (style.pointerEvents === 'none') { return false; } \
+                    const rect = this.getBoundingClientRect(); \
+                    const cx = rect.left + rect.width / 2; \
+                    const cy = rect.top + rect.height / 2; \
+                    const hit = document.elementFromPoint(cx, cy); \
+                    return hit === this || this.contains(hit); \
+                }",
+            )?
+            .as_bool()
+            .unwrap_or(false);
+
+            let previous_url = self.tab.get_url();
+            let mut native_click_effective = false;
+
+            if is_hit_target {
+                // クリックイベントが実際にこの要素まで届いたかどうかを、ページの状態変化に
+                // 頼らずマーカーで観測する(`wait_for`の種類によらず判定できるようにするため)
+                call_js(
+                    "function() { \
+                        this.__scrapeClickMarker = false; \
+                        this.addEventListener('click', () => { this.__scrapeClickMarker = true; }, { once: true, capture: true }); \
+                    }",
+                )?;
+                element.click().map_err(|e| ScrapeError::Interaction {
+                    target: target.to_string(),
+                    source: Box::new(e),
+                })?; // まずはネイティブのクリックを実行
+                sleep(Duration::from_millis(300)); // 反映されるまで少し待つ
+
+                native_click_effective =
+                    call_js("function() { return this.__scrapeClickMarker === true; }")?
+                        .as_bool()
+                        .unwrap_or(false);
+            }
+
+            // 覆われている/pointer-events:noneだった場合、あるいはネイティブクリックが
+            // 要素まで届かなかった場合は、合成クリックでフォールバックする
+            if !native_click_effective {
+                call_js("function() { this.click(); }")?; // 合成クリックで再試行
+            }
+
+            match &options.wait_for {
+                Some(WaitFor::Selector(selector)) => {
+                    self.tab
+                        .wait_for_element_with_custom_timeout(selector, Duration::from_secs(5))
+                        .map_err(|_| ScrapeError::ElementTimeout {
+                            target: target.to_string(),
+                            selector: selector.clone(),
+                        })?;
+                }
+                Some(WaitFor::UrlChange) => {
+                    let mut waited_ms = 0;
+                    while self.tab.get_url() == previous_url && waited_ms < 5000 {
+                        sleep(Duration::from_millis(100));
+                        waited_ms += 100;
+                    }
+                }
+                Some(WaitFor::Stale) => {
+                    let mut waited_ms = 0;
+                    while element.get_box_model().is_ok() && waited_ms < 5000 {
+                        sleep(Duration::from_millis(100));
+                        waited_ms += 100;
+                    }
+                }
+                None => {
+                    // wait-for条件が指定されなかった場合は、従来どおりナビゲーションを待つ
+                    self.tab.wait_until_navigated().map_err(ScrapeError::other)?;
+                }
+            }
             Ok(())
         };
         let result = retry(func, 5, 2)?; // 必要に応じてリトライ
@@ -210,10 +647,13 @@ impl ScrapingWrapper {
     }
 
     /// 指定された要素の内部テキストを取得
-    pub fn get_inner_text(&self, target: &str) -> Result<String, Box<dyn Error>> {
+    pub fn get_inner_text(&self, target: &str) -> Result<String, ScrapeError> {
         let func = || {
             let element = self.get_dom(target)?; // DOM要素を取得
-            let text = element.get_inner_text()?; // 内部テキストを取得
+            let text = element.get_inner_text().map_err(|e| ScrapeError::Interaction {
+                target: target.to_string(),
+                source: Box::new(e),
+            })?; // 内部テキストを取得
             Ok(text)
         };
         let result = retry(func, 5, 2)?; // 必要に応じてリトライ
@@ -221,22 +661,86 @@ impl ScrapingWrapper {
     }
 
     /// 指定されたコンテンツでテキストボックスを埋める
-    pub fn fill_textbox(&self, element: Element, content: String) -> Result<(), Box<dyn Error>> {
+    pub fn fill_textbox(&self, element: Element, content: String) -> Result<(), ScrapeError> {
         let func = || {
-            element.type_into(&content)?; // テキストボックスにコンテンツを入力
+            element.type_into(&content).map_err(ScrapeError::other)?; // テキストボックスにコンテンツを入力
             Ok(())
         };
         let result = retry(func, 5, 2)?; // 必要に応じてリトライ
         Ok(result)
     }
 
+    /// `target`で指定された`<select>`要素の選択肢を`value -> 表示テキスト`のマップとして取得する
+    pub fn get_select_options(&self, target: &str) -> Result<HashMap<String, String>, ScrapeError> {
+        let element = self.get_dom(target)?;
+        let result = element
+            .call_js_fn(
+                "function() { \
+                const options = {}; \
+                for (const opt of this.options) { options[opt.value] = opt.text; } \
+                return options; \
+            }",
+                vec![],
+                false,
+            )
+            .map_err(ScrapeError::other)?;
+        let value = result.value.ok_or_else(|| ScrapeError::JsEvaluation {
+            target: target.to_string(),
+            message: "選択肢の取得に失敗しました".to_string(),
+        })?;
+        let options: HashMap<String, String> =
+            serde_json::from_value(value).map_err(ScrapeError::other)?;
+        Ok(options)
+    }
+
+    /// `target`で指定された`<select>`要素で、`value`属性が一致する選択肢を選ぶ
+    pub fn select_option_by_value(&self, target: &str, value: &str) -> Result<(), ScrapeError> {
+        let element = self.get_dom(target)?;
+        element
+            .call_js_fn(
+                &format!(
+                    "function() {{ \
+                    this.value = {}; \
+                    this.dispatchEvent(new Event('change', {{ bubbles: true }})); \
+                }}",
+                    serde_json::to_string(value).map_err(ScrapeError::other)?
+                ),
+                vec![],
+                false,
+            )
+            .map_err(ScrapeError::other)?;
+        Ok(())
+    }
+
+    /// `target`で指定された`<select>`要素で、表示テキストが一致する選択肢を選ぶ
+    pub fn select_option_by_text(&self, target: &str, text: &str) -> Result<(), ScrapeError> {
+        let element = self.get_dom(target)?;
+        element
+            .call_js_fn(
+                &format!(
+                    "function() {{ \
+                    const targetText = {}; \
+                    for (const opt of this.options) {{ \
+                        if (opt.text === targetText) {{ this.value = opt.value; break; }} \
+                    }} \
+                    this.dispatchEvent(new Event('change', {{ bubbles: true }})); \
+                }}",
+                    serde_json::to_string(text).map_err(ScrapeError::other)?
+                ),
+                vec![],
+                false,
+            )
+            .map_err(ScrapeError::other)?;
+        Ok(())
+    }
+
     /// テキストコンテンツ、オプションのタグ名、およびインデックスでDOM要素を取得
     pub fn get_dom_by_text(
         &self,
         search_text: &str,
         tag_name: Option<&str>,
         index: Option<i8>,
-    ) -> Result<Element, Box<dyn Error>> {
+    ) -> Result<Element, ScrapeError> {
         let func = || {
             let _tag_name = tag_name.unwrap_or("*"); // デフォルトは任意のタグ
             let _index = index.unwrap_or(1); // デフォルトは最初の要素
@@ -247,37 +751,256 @@ impl ScrapingWrapper {
             ); // XPathを構築
             let element = self
                 .tab
-                .wait_for_xpath_with_custom_timeout(_xpath, Duration::from_secs(1))?; // 要素を待機
-            element.scroll_into_view()?; // 要素を表示領域にスクロール
+                .wait_for_xpath_with_custom_timeout(_xpath, Duration::from_secs(1))
+                .map_err(|_| ScrapeError::ElementTimeout {
+                    target: search_text.to_string(),
+                    selector: _xpath.clone(),
+                })?; // 要素を待機
+            element.scroll_into_view().map_err(ScrapeError::other)?; // 要素を表示領域にスクロール
             Ok(element)
         };
         let result = retry(func, 5, 2)?; // 必要に応じてリトライ
         Ok(result)
     }
 
-    pub fn close_tab(&self) -> Result<(), Box<dyn Error>> {
-        self.tab.close(true)?;
+    pub fn close_tab(&self) -> Result<(), ScrapeError> {
+        self.tab.close(true).map_err(ScrapeError::other)?;
         Ok(())
     }
 
-    /// 一連の操作を実行
-    pub fn operate(&self, operations: Vec<Operation>) -> Result<(), Box<dyn Error>> {
-        for operation in operations {
-            match operation.method {
-                OperationMethod::Go => {
-                    self.go(&operation.target)?; // URLに移動
-                }
-                OperationMethod::Click => {
-                    let element = self.get_dom(&operation.target)?; // 要素を取得
-                    self.click(element)?; // 要素をクリック
+    /// URLの一部が`url_substring`に一致するレスポンスのボディをキャプチャする
+    ///
+    /// レンダリング済みのDOMではなく、XHR/fetchで取得されたJSONなどを
+    /// そのまま取得したい場合に使用する。`url_substring`は呼び出された時点で
+    /// 監視対象として登録され、以後その部分文字列にマッチするレスポンスのみ
+    /// ボディが記録される(全レスポンスを無条件にキャプチャするわけではない)。
+    /// そのため`capture_response`は対象の操作(ナビゲーションやクリックなど)より
+    /// 前に一度呼んでおく必要がある。
+    pub fn capture_response(&self, url_substring: &str) -> Result<String, ScrapeError> {
+        self.captured_responses
+            .lock()
+            .unwrap()
+            .entry(url_substring.to_string())
+            .or_insert(None);
+
+        let func = || {
+            self.captured_responses
+                .lock()
+                .unwrap()
+                .get(url_substring)
+                .cloned()
+                .flatten()
+                .ok_or_else(|| {
+                    ScrapeError::Other("対象のレスポンスはまだキャプチャされていません".into())
+                })
+        };
+        retry(func, 10, 1) // レスポンスが届くまでリトライ
+    }
+
+    /// サイト固有のExtractorを登録する
+    ///
+    /// 後から登録したものほど優先される(`extract`は先頭から順に`can_handle`を試す)。
+    /// 常にマッチする`DomDefsExtractor`が最後のフォールバックとして残る。
+    pub fn register_extractor(&self, extractor: Arc<dyn Extractor>) {
+        self.extractors.lock().unwrap().insert(0, extractor);
+    }
+
+    /// 現在のタブのURLに一致する最初のExtractorを実行し、構造化データを返す
+    pub fn extract(&self) -> Result<serde_json::Value, ScrapeError> {
+        let url = self.tab.get_url();
+        // `extractor.extract(self)`はExtractor自身が(別サイト向けの委譲などで)`extract`を
+        // 再帰的に呼び出す可能性があるため、ロックを持ったまま呼び出すとデッドロックしうる。
+        // 該当するExtractorを`Arc`ごと複製してロックを解放してから実行する。
+        let extractor = {
+            let extractors = self.extractors.lock().unwrap();
+            extractors
+                .iter()
+                .find(|extractor| extractor.can_handle(&url))
+                .cloned()
+                .ok_or_else(|| ScrapeError::NoMatchingExtractor(url.clone()))?
+        };
+        extractor.extract(self)
+    }
+
+    /// 現在表示しているページの`<a href>`リンクを全て取得する
+    fn discover_links(&self) -> Result<Vec<String>, ScrapeError> {
+        let result = self
+            .tab
+            .evaluate(
+                "Array.from(document.querySelectorAll('a[href]')).map(a => a.href)",
+                false,
+            )
+            .map_err(ScrapeError::other)?;
+        let value = result.value.ok_or_else(|| ScrapeError::JsEvaluation {
+            target: "a[href]".to_string(),
+            message: "リンク一覧の取得に失敗しました".to_string(),
+        })?;
+        let links: Vec<String> = serde_json::from_value(value).map_err(ScrapeError::other)?;
+        Ok(links)
+    }
+
+    /// シードURLから幅優先でリンクを辿ってクロールする
+    ///
+    /// 訪問済みURLは正規化した上で`HashSet`に記録し、同じページを何度も
+    /// 訪れないようにする。ページ遷移のたびに`[min_delay_ms, max_delay_ms]`
+    /// からランダムに選んだ時間だけ待機し、人間のブラウジングに近づける。
+    /// 1ページの失敗はクロール全体を中断せず、`CrawlSummary::errors`に記録される。
+    pub fn crawl<F>(
+        &self,
+        seed_url: &str,
+        options: CrawlOptions,
+        mut visitor: F,
+    ) -> Result<CrawlSummary, ScrapeError>
+    where
+        F: FnMut(&ScrapingWrapper, &str) -> Result<(), ScrapeError>,
+    {
+        if options.min_delay_ms > options.max_delay_ms {
+            return Err(ScrapeError::InvalidArgument(format!(
+                "min_delay_ms({})がmax_delay_ms({})を超えています",
+                options.min_delay_ms, options.max_delay_ms
+            )));
+        }
+
+        let seed = Url::parse(seed_url).map_err(ScrapeError::other)?;
+        let mut summary = CrawlSummary::default();
+        // キューに積んだ時点で記録する(ポップ時にしか記録しないと、複数の親ページから
+        // 同じリンクが見つかるたびに重複してキューへ積まれてしまう)
+        let mut visited_set: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<(String, u32)> = VecDeque::new();
+        visited_set.insert(seed_url.trim_end_matches('/').to_string());
+        queue.push_back((seed_url.to_string(), 0));
+
+        while let Some((url, depth)) = queue.pop_front() {
+            if let Err(e) = self.go(&url) {
+                summary.errors.insert(url.clone(), e.to_string());
+                continue;
+            }
+            summary.visited.push(url.clone());
+
+            if let Err(e) = visitor(self, &url) {
+                summary.errors.insert(url.clone(), e.to_string());
+            }
+
+            if depth < options.max_depth {
+                // 相対リンクは現在のページのURLを基準に解決する(シードのパスを基準にすると、
+                // 深い階層のページが出す相対hrefが誤った絶対URLになってしまう)
+                let current = Url::parse(&url).map_err(ScrapeError::other)?;
+                match self.discover_links() {
+                    Ok(links) => {
+                        for link in links {
+                            let link_url = match Url::parse(&link) {
+                                Ok(parsed) => parsed,
+                                Err(_) => match current.join(&link) {
+                                    Ok(parsed) => parsed,
+                                    Err(_) => continue, // 解決できないリンクは無視
+                                },
+                            };
+                            if options.same_host_only && link_url.host_str() != seed.host_str() {
+                                continue;
+                            }
+                            if let Some(filter) = &options.url_filter {
+                                if !filter.is_match(link_url.as_str()) {
+                                    continue;
+                                }
+                            }
+                            if let Some(exclude_filter) = &options.exclude_filter {
+                                if exclude_filter.is_match(link_url.as_str()) {
+                                    continue;
+                                }
+                            }
+                            let normalized_link =
+                                link_url.as_str().trim_end_matches('/').to_string();
+                            if !visited_set.contains(&normalized_link) {
+                                visited_set.insert(normalized_link);
+                                queue.push_back((link_url.to_string(), depth + 1));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        summary.errors.insert(url.clone(), e.to_string());
+                    }
                 }
-                OperationMethod::Fill => {
-                    if let Some(content) = operation.content {
+            }
+
+            if !queue.is_empty() {
+                let delay_ms = rand::thread_rng()
+                    .gen_range(options.min_delay_ms..=options.max_delay_ms);
+                sleep(Duration::from_millis(delay_ms)); // 人間らしいランダムな待機
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// 現在のセッションのCookieをエクスポートする
+    ///
+    /// 戻り値はJSONへシリアライズできるので、ファイルに保存しておけば
+    /// 認証済みセッションを別の実行でも`import_cookies`からそのまま復元できる。
+    pub fn export_cookies(&self) -> Result<Vec<Network::Cookie>, ScrapeError> {
+        self.tab.get_cookies().map_err(ScrapeError::other)
+    }
+
+    /// `export_cookies`でエクスポートしたCookieをセッションへインポートする
+    ///
+    /// `Network::Cookie`(取得時の型)と`set_cookies`が要求する`Network::CookieParam`は
+    /// CDP上ほぼ同じ情報を持つが型が異なるため、内部で詰め替えてから渡す。
+    pub fn import_cookies(&self, cookies: Vec<Network::Cookie>) -> Result<(), ScrapeError> {
+        let cookies = cookies.into_iter().map(cookie_to_param).collect();
+        self.tab.set_cookies(cookies).map_err(ScrapeError::other)?;
+        Ok(())
+    }
+
+    /// 表示領域(ビューポート)全体、または`target`で指定した`dom_defs`の要素をPNG画像として撮影する
+    pub fn screenshot_png(&self, target: Option<&str>) -> Result<Vec<u8>, ScrapeError> {
+        match target {
+            Some(target) => {
+                let element = self.get_dom(target)?;
+                element
+                    .capture_screenshot(CaptureScreenshotFormatOption::Png)
+                    .map_err(ScrapeError::other)
+            }
+            None => self
+                .tab
+                .capture_screenshot(CaptureScreenshotFormatOption::Png, None, None, true)
+                .map_err(ScrapeError::other),
+        }
+    }
+
+    /// 現在のページをPDFとして出力する
+    pub fn print_to_pdf(&self, opts: Option<PrintToPdfOptions>) -> Result<Vec<u8>, ScrapeError> {
+        self.tab.print_to_pdf(opts).map_err(ScrapeError::other)
+    }
+
+    /// 一連の操作を実行
+    ///
+    /// 最初に失敗したステップで処理を中断し、それが何番目の・どの種類の操作かを
+    /// `ScrapeError::OperateStep`として報告する。
+    pub fn operate(&self, operations: Vec<Operation>) -> Result<(), ScrapeError> {
+        for (index, operation) in operations.into_iter().enumerate() {
+            let method_name = format!("{:?}", operation.method);
+            let step_result = (|| -> Result<(), ScrapeError> {
+                match operation.method {
+                    OperationMethod::Go => {
+                        self.go(&operation.target)?; // URLに移動
+                    }
+                    OperationMethod::Click => {
                         let element = self.get_dom(&operation.target)?; // 要素を取得
-                        self.fill_textbox(element, content)?; // テキストボックスを埋める
+                        self.click(element)?; // 要素をクリック
+                    }
+                    OperationMethod::Fill => {
+                        if let Some(content) = operation.content {
+                            let element = self.get_dom(&operation.target)?; // 要素を取得
+                            self.fill_textbox(element, content)?; // テキストボックスを埋める
+                        }
                     }
                 }
-            }
+                Ok(())
+            })();
+            step_result.map_err(|e| ScrapeError::OperateStep {
+                index,
+                method: method_name,
+                source: Box::new(e),
+            })?;
         }
         Ok(())
     }
@@ -287,6 +1010,57 @@ impl ScrapingWrapper {
 mod tests {
     use super::*;
 
+    /// `routes`に登録したパスにはそのHTMLを、それ以外のパスには`not found`という本文を返す、テスト用の簡易HTTPサーバを
+    /// 127.0.0.1の空きポートで起動し、そのベースURL(末尾スラッシュなし)を返す。
+    ///
+    /// 外部サイトに依存せず、かつ実際のHTTPレスポンスとしてページを提供することで、
+    /// `discover_links`や`click_with`が本物のDOM/ネットワーク越しに動作することを確認できる。
+    fn start_fixture_server(routes: HashMap<&'static str, &'static str>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("テスト用サーバの起動に失敗しました");
+        let port = listener.local_addr().unwrap().port();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                let path = request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or("/")
+                    .to_string();
+                // ヘッダー部分を読み飛ばす(空行がヘッダーの終端)
+                loop {
+                    let mut line = String::new();
+                    match reader.read_line(&mut line) {
+                        Ok(0) => break,
+                        Ok(_) if line == "\r\n" || line == "\n" => break,
+                        Ok(_) => continue,
+                        Err(_) => break,
+                    }
+                }
+                let body = routes.get(path.as_str()).copied().unwrap_or("not found");
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        format!("http://127.0.0.1:{}", port)
+    }
+
     #[test]
     fn use_exist_browser() {
         // 初期化
@@ -295,6 +1069,9 @@ mod tests {
             headless: Some(false),
             window_size: Some((1920, 1080)),
             port_number: Some(9222),
+            user_agent: None,
+            extra_headers: None,
+            block_resource_types: None,
         };
         let wrapper = ScrapingWrapper::new(scrape_options).unwrap();
         wrapper.go("https://google.com").unwrap();
@@ -321,6 +1098,9 @@ mod tests {
             headless: Some(false),
             window_size: Some((1920, 1080)),
             port_number: None,
+            user_agent: None,
+            extra_headers: None,
+            block_resource_types: None,
         };
         let wrapper = ScrapingWrapper::new(scrape_options).unwrap();
 
@@ -350,4 +1130,156 @@ mod tests {
         let first_result = wrapper.get_inner_text("first_result").unwrap();
         println!("first_result: {}", first_result);
     }
+
+    #[test]
+    fn click_with_falls_back_to_synthetic_click_when_native_click_has_no_effect() {
+        // 透明なオーバーレイがボタン全体を覆っているテストページ。
+        // ネイティブクリックは中心点でオーバーレイに命中するため、ボタン自身のクリック
+        // ハンドラは呼ばれず、合成クリック(`this.click()`)のフォールバックでのみ
+        // `#clicked-marker`が追加される。
+        let base_url = start_fixture_server(HashMap::from([(
+            "/",
+            "<html><body>\
+                <button id=\"covered-button\">click me</button>\
+                <div id=\"overlay\" style=\"position:fixed;top:0;left:0;width:100%;height:100%;\"></div>\
+                <script>\
+                    document.getElementById('covered-button').addEventListener('click', function () {\
+                        var marker = document.createElement('div');\
+                        marker.id = 'clicked-marker';\
+                        document.body.appendChild(marker);\
+                    });\
+                </script>\
+            </body></html>",
+        )]));
+
+        let dom_defs = HashMap::from([(
+            "covered_button".to_string(),
+            "#covered-button".to_string(),
+        )]);
+        let scrape_options = ScrapeOption {
+            dom_defs: Some(dom_defs),
+            headless: Some(true),
+            window_size: Some((1280, 800)),
+            port_number: None,
+            user_agent: None,
+            extra_headers: None,
+            block_resource_types: None,
+        };
+        let wrapper = ScrapingWrapper::new(scrape_options).unwrap();
+
+        wrapper.go(&base_url).unwrap();
+        let element = wrapper.get_dom("covered_button").unwrap();
+        wrapper
+            .click_with(
+                element,
+                ClickOptions {
+                    wait_for: Some(WaitFor::Selector("#clicked-marker".to_string())),
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn crawl_visits_each_url_once_and_respects_max_depth() {
+        // /a -> /b -> (/a, /c) -> (/a, /d) というリンクグラフ。
+        // - /b, /c はともに既訪問の/aへ戻るリンクを持つ(重複排除の確認)
+        // - /d は/cのさらに先(深さ3)にしか存在しないため、max_depth=2では辿られない(深さ制限の確認)
+        let base_url = start_fixture_server(HashMap::from([
+            ("/a", "<html><body><a href=\"/b\">b</a></body></html>"),
+            (
+                "/b",
+                "<html><body><a href=\"/a\">a</a><a href=\"/c\">c</a></body></html>",
+            ),
+            (
+                "/c",
+                "<html><body><a href=\"/a\">a</a><a href=\"/d\">d</a></body></html>",
+            ),
+            ("/d", "<html><body>unreachable within max_depth</body></html>"),
+        ]));
+
+        let scrape_options = ScrapeOption {
+            dom_defs: None,
+            headless: Some(true),
+            window_size: Some((1280, 800)),
+            port_number: None,
+            user_agent: None,
+            extra_headers: None,
+            block_resource_types: None,
+        };
+        let wrapper = ScrapingWrapper::new(scrape_options).unwrap();
+
+        let options = CrawlOptions {
+            max_depth: 2,
+            same_host_only: true,
+            url_filter: None,
+            exclude_filter: None,
+            min_delay_ms: 0,
+            max_delay_ms: 10,
+        };
+        let visit_counts: Arc<Mutex<HashMap<String, u32>>> = Arc::new(Mutex::new(HashMap::new()));
+        let visit_counts_for_visitor = visit_counts.clone();
+        let summary = wrapper
+            .crawl(&format!("{}/a", base_url), options, move |_wrapper, url| {
+                *visit_counts_for_visitor
+                    .lock()
+                    .unwrap()
+                    .entry(url.to_string())
+                    .or_insert(0) += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(summary.errors.is_empty(), "errors: {:?}", summary.errors);
+
+        // /a, /b, /cは訪問されるが、深さ制限を超える/dは訪問されない
+        let mut visited_paths: Vec<String> = summary
+            .visited
+            .iter()
+            .map(|url| url.trim_start_matches(base_url.as_str()).to_string())
+            .collect();
+        visited_paths.sort();
+        assert_eq!(visited_paths, vec!["/a", "/b", "/c"]);
+
+        // /b, /cの両方が既訪問の/aへリンクしているが、/aは一度しか訪問されない
+        // (訪問済みセットがキュー投入時に更新され、重複して再キューイングされないことの確認)
+        let counts = visit_counts.lock().unwrap();
+        for path in ["/a", "/b", "/c"] {
+            assert_eq!(
+                counts.get(&format!("{}{}", base_url, path)).copied(),
+                Some(1),
+                "{} was visited more than once",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn extract_prefers_the_most_recently_registered_extractor() {
+        // 後から登録したExtractorが、生成時に登録済みのDomDefsExtractorより優先されることを確認する
+        struct AlwaysJsonExtractor;
+        impl Extractor for AlwaysJsonExtractor {
+            fn can_handle(&self, _url: &str) -> bool {
+                true
+            }
+            fn extract(&self, _wrapper: &ScrapingWrapper) -> Result<serde_json::Value, ScrapeError> {
+                Ok(serde_json::json!({ "from": "AlwaysJsonExtractor" }))
+            }
+        }
+
+        let scrape_options = ScrapeOption {
+            dom_defs: None,
+            headless: Some(false),
+            window_size: Some((1920, 1080)),
+            port_number: None,
+            user_agent: None,
+            extra_headers: None,
+            block_resource_types: None,
+        };
+        let wrapper = ScrapingWrapper::new(scrape_options).unwrap();
+        wrapper.go("https://example.com/").unwrap();
+
+        wrapper.register_extractor(Arc::new(AlwaysJsonExtractor));
+        let extracted = wrapper.extract().unwrap();
+        assert_eq!(extracted, serde_json::json!({ "from": "AlwaysJsonExtractor" }));
+    }
 }